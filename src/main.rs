@@ -1,9 +1,15 @@
 use chrono::{Datelike, Local};
 use dotenv::dotenv;
 use reqwest::blocking::Client;
+use rspotify::clients::BaseClient;
+use rspotify::model::AlbumId;
+use rspotify::{ClientCredsSpotify, Credentials};
 use serde::Serialize;
 use std::env;
+use std::fmt;
 use std::{thread, time::Duration};
+use teloxide::prelude::*;
+use teloxide::types::InputFile;
 
 const GENERATOR_URL: &str = "https://1001albumsgenerator.com";
 const GROUPME_API_URL: &str = "https://api.groupme.com/v3/bots/post";
@@ -15,12 +21,280 @@ struct Album {
     artist: String,
     release_year: String,
     spotify_link: String,
+    spotify_meta: Option<SpotifyMeta>,
+    group_state: Option<GroupState>,
+}
+
+#[derive(Debug)]
+struct SpotifyMeta {
+    track_count: usize,
+    runtime_minutes: u64,
+    label: Option<String>,
+    tracklist: Vec<String>,
+    cover_art_url: Option<String>,
+}
+
+#[derive(Debug)]
+struct GroupState {
+    average_rating: Option<f64>,
+    previous_pick: Option<PreviousPick>,
+}
+
+#[derive(Debug)]
+struct PreviousPick {
+    album: String,
+    artist: String,
+    rating: f64,
+}
+
+// `previous_pick` needs the current album's position in `albums` (absent
+// while voting is still open), but `average_rating` doesn't, so it's computed
+// independently rather than bailing out with it via `?`.
+fn get_group_state(json: &serde_json::Value) -> Option<GroupState> {
+    let albums = json["albums"].as_array()?;
+
+    let average_rating = json["averageRating"].as_f64().or_else(|| {
+        let ratings: Vec<f64> = albums.iter().filter_map(|a| a["rating"].as_f64()).collect();
+        if ratings.is_empty() {
+            None
+        } else {
+            Some(ratings.iter().sum::<f64>() / ratings.len() as f64)
+        }
+    });
+
+    let previous_pick = json["currentAlbum"]["spotifyId"]
+        .as_str()
+        .and_then(|current_id| {
+            let current_idx = albums
+                .iter()
+                .position(|a| a["spotifyId"].as_str() == Some(current_id))?;
+            let prev = albums.get(current_idx.checked_sub(1)?)?;
+            Some(PreviousPick {
+                album: prev["name"].as_str()?.to_string(),
+                artist: prev["artist"].as_str()?.to_string(),
+                rating: prev["rating"].as_f64()?,
+            })
+        });
+
+    if average_rating.is_none() && previous_pick.is_none() {
+        return None;
+    }
+
+    Some(GroupState {
+        average_rating,
+        previous_pick,
+    })
+}
+
+fn get_spotify_meta(spotify_id: &str) -> Option<SpotifyMeta> {
+    let client_id = env::var("SPOTIFY_CLIENT_ID").ok()?;
+    let client_secret = env::var("SPOTIFY_CLIENT_SECRET").ok()?;
+
+    let creds = Credentials::new(&client_id, &client_secret);
+    let spotify = ClientCredsSpotify::new(creds);
+    if let Err(e) = spotify.request_token() {
+        println!("Could not authenticate with Spotify: {}", e);
+        return None;
+    }
+
+    let album_id = match AlbumId::from_id(spotify_id) {
+        Ok(id) => id,
+        Err(e) => {
+            println!("Could not parse Spotify album id {}: {}", spotify_id, e);
+            return None;
+        }
+    };
+
+    let album = match spotify.album(album_id.clone(), None) {
+        Ok(album) => album,
+        Err(e) => {
+            println!("Could not fetch Spotify album metadata: {}", e);
+            return None;
+        }
+    };
+
+    // The album endpoint only returns one page of tracks (20, up to 50), which
+    // double LPs, live albums, and compilations routinely exceed, so the full
+    // tracklist is paginated separately rather than read off `album.tracks`.
+    let mut tracks = Vec::new();
+    for track in spotify.album_track(album_id, None) {
+        match track {
+            Ok(t) => tracks.push(t),
+            Err(e) => {
+                println!("Could not fetch full tracklist: {}", e);
+                return None;
+            }
+        }
+    }
+    let runtime_ms: u64 = tracks.iter().map(|t| t.duration.num_milliseconds() as u64).sum();
+
+    Some(SpotifyMeta {
+        track_count: album.tracks.total as usize,
+        runtime_minutes: runtime_ms / 1000 / 60,
+        label: album.label,
+        tracklist: tracks.iter().map(|t| t.name.clone()).collect(),
+        cover_art_url: album.images.first().map(|i| i.url.clone()),
+    })
 }
 
 #[derive(Serialize)]
 struct Message {
     bot_id: String,
     text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    picture_url: Option<String>,
+}
+
+#[derive(Debug)]
+enum SinkError {
+    Reqwest(reqwest::Error),
+    Telegram(teloxide::RequestError),
+}
+
+impl fmt::Display for SinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SinkError::Reqwest(e) => write!(f, "{}", e),
+            SinkError::Telegram(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for SinkError {
+    fn from(e: reqwest::Error) -> Self {
+        SinkError::Reqwest(e)
+    }
+}
+
+impl From<teloxide::RequestError> for SinkError {
+    fn from(e: teloxide::RequestError) -> Self {
+        SinkError::Telegram(e)
+    }
+}
+
+impl std::error::Error for SinkError {}
+
+// A destination the daily album message gets posted to. `cover_art_url`
+// is optional and may be ignored by sinks that can't render an image.
+trait MessageSink {
+    fn send(&self, message: &str, cover_art_url: Option<&str>) -> Result<(), SinkError>;
+}
+
+struct GroupMeSink {
+    client: Client,
+    bot_id: String,
+    retry_limit: u8,
+    sleep_secs: u64,
+}
+
+impl MessageSink for GroupMeSink {
+    fn send(&self, message: &str, cover_art_url: Option<&str>) -> Result<(), SinkError> {
+        let json = Message {
+            bot_id: self.bot_id.clone(),
+            text: message.to_string(),
+            picture_url: cover_art_url.map(|u| u.to_string()),
+        };
+
+        let mut retry: u8 = 0;
+        loop {
+            let resp = self.client.post(GROUPME_API_URL).json(&json).send()?;
+            let wait_secs = retry_after_secs(&resp, self.sleep_secs);
+            let resp = resp.error_for_status();
+            match resp {
+                Ok(_) => {
+                    println!("Message sent to GroupMe:\n{}", message);
+                    return Ok(());
+                }
+                Err(e) => {
+                    if retry < self.retry_limit {
+                        retry += 1;
+                        println!("Could not send message to GroupMe: {}", e);
+                        println!(
+                            "Waiting {} seconds and retrying... (Retry {}/{})",
+                            wait_secs, retry, self.retry_limit,
+                        );
+                        thread::sleep(Duration::from_secs(wait_secs));
+                    } else {
+                        return Err(e.into());
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct TelegramSink {
+    bot_token: String,
+    chat_id: ChatId,
+    retry_limit: u8,
+    sleep_secs: u64,
+}
+
+impl MessageSink for TelegramSink {
+    fn send(&self, message: &str, cover_art_url: Option<&str>) -> Result<(), SinkError> {
+        let rt = tokio::runtime::Runtime::new().expect("Could not start Tokio runtime");
+        // A malformed URL falls back to plain text rather than panicking.
+        let cover_art_url = cover_art_url.and_then(|url| match url.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                println!("Could not parse cover art URL {}: {}", url, e);
+                None
+            }
+        });
+
+        rt.block_on(async {
+            let bot = Bot::new(&self.bot_token);
+
+            let mut retry: u8 = 0;
+            loop {
+                // When cover art is available, post it as a photo with the
+                // message as its caption; otherwise fall back to plain text.
+                let send_result = match &cover_art_url {
+                    Some(url) => bot
+                        .send_photo(self.chat_id, InputFile::url(url.clone()))
+                        .caption(message)
+                        .await
+                        .map(|_| ()),
+                    None => bot.send_message(self.chat_id, message).await.map(|_| ()),
+                };
+                match send_result {
+                    Ok(_) => {
+                        println!("Message sent to Telegram:\n{}", message);
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        if retry < self.retry_limit {
+                            retry += 1;
+                            let wait_secs = match &e {
+                                teloxide::RequestError::RetryAfter(secs) => secs.as_secs(),
+                                _ => self.sleep_secs,
+                            };
+                            println!("Could not send message to Telegram: {}", e);
+                            println!(
+                                "Waiting {} seconds and retrying... (Retry {}/{})",
+                                wait_secs, retry, self.retry_limit,
+                            );
+                            tokio::time::sleep(Duration::from_secs(wait_secs)).await;
+                        } else {
+                            return Err(e.into());
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+// Honors `Retry-After` (seconds form) on a 429, falling back to `sleep_secs`.
+fn retry_after_secs(resp: &reqwest::blocking::Response, sleep_secs: u64) -> u64 {
+    if resp.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return sleep_secs;
+    }
+    resp.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(sleep_secs)
 }
 
 fn get_album(
@@ -31,10 +305,16 @@ fn get_album(
 ) -> Result<Album, reqwest::Error> {
     let mut retry: u8 = 0;
     loop {
-        let resp = client.get(generator_api_url).send()?.error_for_status();
+        let resp = client.get(generator_api_url).send()?;
+        let wait_secs = retry_after_secs(&resp, sleep_secs);
+        let resp = resp.error_for_status();
         match resp {
             Ok(r) => {
                 let json = r.json::<serde_json::Value>()?;
+                let spotify_id = json["currentAlbum"]["spotifyId"]
+                    .as_str()
+                    .unwrap()
+                    .to_string();
                 let album = Album {
                     album: json["currentAlbum"]["name"].as_str().unwrap().to_string(),
                     artist: json["currentAlbum"]["artist"].as_str().unwrap().to_string(),
@@ -42,11 +322,9 @@ fn get_album(
                         .as_str()
                         .unwrap()
                         .to_string(),
-                    spotify_link: format!(
-                        "{}/{}",
-                        SPOTIFY_URL,
-                        json["currentAlbum"]["spotifyId"].as_str().unwrap()
-                    ),
+                    spotify_link: format!("{}/{}", SPOTIFY_URL, spotify_id),
+                    spotify_meta: get_spotify_meta(&spotify_id),
+                    group_state: get_group_state(&json),
                 };
                 return Ok(album);
             }
@@ -56,9 +334,9 @@ fn get_album(
                     println!("Could not get album: {}", e);
                     println!(
                         "Waiting {} seconds and retrying... (Retry {}/{})",
-                        sleep_secs, retry, retry_limit,
+                        wait_secs, retry, retry_limit,
                     );
-                    thread::sleep(Duration::from_secs(sleep_secs));
+                    thread::sleep(Duration::from_secs(wait_secs));
                 } else {
                     return Err(e);
                 }
@@ -70,74 +348,113 @@ fn get_album(
 fn get_message(album: &Album, generator_group_url: &str) -> String {
     let dt = Local::now();
 
-    let message = format!(
+    let mut message = format!(
         "1001albumsgenerator {}/{}/{}\n\n\
-        {} by {} ({})\n\n\
-        {}\n\n\
-        Group: {}\n",
+        {} by {} ({})\n\n",
         dt.month(),
         dt.day(),
         dt.year(),
         album.album,
         album.artist,
         album.release_year,
-        album.spotify_link,
-        generator_group_url,
     );
 
+    if let Some(meta) = &album.spotify_meta {
+        let label = meta.label.as_deref().unwrap_or("Unknown label");
+        message.push_str(&format!(
+            "{} tracks \u{b7} {} min \u{b7} {}\n\n",
+            meta.track_count, meta.runtime_minutes, label,
+        ));
+        for (i, track) in meta.tracklist.iter().enumerate() {
+            message.push_str(&format!("{}. {}\n", i + 1, track));
+        }
+        message.push('\n');
+    }
+
+    if let Some(state) = &album.group_state {
+        if let Some(prev) = &state.previous_pick {
+            message.push_str(&format!(
+                "Yesterday's pick, {} by {}, scored {:.1}\n",
+                prev.album, prev.artist, prev.rating,
+            ));
+        }
+        if let Some(avg) = state.average_rating {
+            message.push_str(&format!("Group average rating: {:.1}\n", avg));
+        }
+        message.push('\n');
+    }
+
+    message.push_str(&format!(
+        "{}\n\nGroup: {}\n",
+        album.spotify_link, generator_group_url,
+    ));
+
     return message;
 }
 
-fn send_message(
+// GroupMe is always included; Telegram is added only when
+// TELEGRAM_BOT_TOKEN/TELEGRAM_CHAT_ID are both set.
+fn get_sinks(
     client: &Client,
     bot_id: &str,
-    message: &str,
     retry_limit: u8,
     sleep_secs: u64,
-) -> Result<(), reqwest::Error> {
-    let json = Message {
+) -> Vec<Box<dyn MessageSink>> {
+    let mut sinks: Vec<Box<dyn MessageSink>> = vec![Box::new(GroupMeSink {
+        client: client.clone(),
         bot_id: bot_id.to_string(),
-        text: message.to_string(),
-    };
+        retry_limit,
+        sleep_secs,
+    })];
 
-    let mut retry: u8 = 0;
-    loop {
-        let resp = client
-            .post(GROUPME_API_URL)
-            .json(&json)
-            .send()?
-            .error_for_status();
-        match resp {
-            Ok(_) => {
-                println!("Message sent:\n{}", message);
-                return Ok(());
-            }
-            Err(e) => {
-                if retry < retry_limit {
-                    retry += 1;
-                    println!("Could not send message: {}", e);
-                    println!(
-                        "Waiting {} seconds and retrying... (Retry {}/{})",
-                        sleep_secs, retry, retry_limit,
-                    );
-                    thread::sleep(Duration::from_secs(sleep_secs));
-                } else {
-                    return Err(e);
-                }
-            }
-        }
+    if let (Ok(bot_token), Ok(chat_id)) =
+        (env::var("TELEGRAM_BOT_TOKEN"), env::var("TELEGRAM_CHAT_ID"))
+    {
+        let chat_id: i64 = chat_id
+            .parse()
+            .expect("TELEGRAM_CHAT_ID must be an integer");
+        sinks.push(Box::new(TelegramSink {
+            bot_token,
+            chat_id: ChatId(chat_id),
+            retry_limit,
+            sleep_secs,
+        }));
     }
+
+    sinks
+}
+
+// No-op when SENTRY_DSN is unset. The guard must stay alive for the life of
+// main so buffered events get flushed on drop; the panic integration (on by
+// default) is what actually reports the .expect() failures below.
+fn init_sentry() -> Option<sentry::ClientInitGuard> {
+    let dsn = env::var("SENTRY_DSN").ok()?;
+    Some(sentry::init((
+        dsn,
+        sentry::ClientOptions {
+            release: sentry::release_name!(),
+            ..Default::default()
+        },
+    )))
 }
 
 fn main() {
     dotenv().ok();
 
-    // Create a .env file with the following 2 variables: BOT_ID, GROUP
+    // Create a .env file with the following variables: BOT_ID, GROUP, and
+    // optionally SPOTIFY_CLIENT_ID/SPOTIFY_CLIENT_SECRET,
+    // TELEGRAM_BOT_TOKEN/TELEGRAM_CHAT_ID, SENTRY_DSN
+    let _sentry_guard = init_sentry();
+
     // GroupMe group bot ID
     let bot_id = env::var("BOT_ID").expect("BOT_ID is not set");
     // https://1001albumsgenerator.com/ group name
     let group = env::var("GROUP").expect("GROUP is not set");
 
+    sentry::configure_scope(|scope| {
+        scope.set_tag("group", &group);
+    });
+
     let client = Client::new();
 
     let generator_api_url = format!("{}/api/v1/groups/{}", GENERATOR_URL, group);
@@ -148,9 +465,21 @@ fn main() {
     let album = get_album(&client, &generator_api_url, retry_limit, sleep_secs)
         .expect("Could not get album");
 
+    sentry::configure_scope(|scope| {
+        scope.set_tag("album", &album.album);
+    });
+
     let generator_group_url = format!("{}/groups/{}", GENERATOR_URL, group);
     let message = get_message(&album, &generator_group_url);
 
-    send_message(&client, &bot_id, &message, retry_limit, sleep_secs)
-        .expect("Could not send message");
+    let cover_art_url = album
+        .spotify_meta
+        .as_ref()
+        .and_then(|m| m.cover_art_url.as_deref());
+
+    let sinks = get_sinks(&client, &bot_id, retry_limit, sleep_secs);
+    for sink in sinks {
+        sink.send(&message, cover_art_url)
+            .expect("Could not send message");
+    }
 }